@@ -0,0 +1,170 @@
+//! issuer attestation verification for the `certificate` metadata profile.  This whole
+//! module is only meaningful when the `certificate` Cargo feature is enabled, since it
+//! operates on `CertificateInfo`, which only exists under that feature.
+
+#![cfg(feature = "certificate")]
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Api, CanonicalAddr, StdError, StdResult};
+
+use sha2::{Digest, Sha256};
+
+use crate::crypto_utils::address_from_secp256k1_pubkey;
+use crate::token::{Attestation, AttestationScheme, CertificateInfo};
+
+/// Builds the canonical byte message that an issuer signs to attest a certificate.
+///
+/// Fields are concatenated in this fixed order, each preceded by its length as a 4-byte
+/// little-endian `u32`, so that variable-length fields can never be confused with one
+/// another: `cert_number`, `name`, `cert_type`, `issue_date`, `issuer_id`, `recipient_id`.
+/// Absent `Option` fields are treated as empty strings.  This exact byte layout must be
+/// reproduced off-chain by any signer or verifier, so it must never change without a new
+/// `AttestationScheme` variant.
+pub fn canonical_certificate_message(cert: &CertificateInfo, recipient_id: &str) -> Vec<u8> {
+    let mut msg: Vec<u8> = Vec::new();
+    for field in [
+        cert.cert_number.as_str(),
+        cert.name.as_deref().unwrap_or_default(),
+        cert.cert_type.as_deref().unwrap_or_default(),
+        cert.issue_date.as_deref().unwrap_or_default(),
+        cert.issuer_id.as_deref().unwrap_or_default(),
+        recipient_id,
+    ] {
+        let bytes = field.as_bytes();
+        msg.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        msg.extend_from_slice(bytes);
+    }
+    msg
+}
+
+/// sha256 digest of the canonical certificate message, which is what is actually signed
+pub fn certificate_message_hash(cert: &CertificateInfo, recipient_id: &str) -> Vec<u8> {
+    Sha256::digest(canonical_certificate_message(cert, recipient_id)).to_vec()
+}
+
+/// result of checking an `Attestation` against a certificate, returned by the
+/// `VerifyCertificate` query
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct CertificateVerification {
+    /// true if the signature is valid for the certificate's canonical message
+    pub verified: bool,
+    /// canonical address recovered from the attestation's public key
+    pub issuer_address: CanonicalAddr,
+}
+
+/// Verifies that `cert.attestation` is a valid signature over the certificate's canonical
+/// message and that it was produced by `public_key`.  Callers are responsible for checking
+/// the recovered issuer address against the issuer registry before trusting the result.
+///
+/// Returns `ContractError`-equivalent `StdError::generic_err` because this contract's mint
+/// path should reject the mint outright when verification fails; it does not silently
+/// return `verified: false` except from the read-only `VerifyCertificate` query, where
+/// `verify_certificate_attestation_unchecked` below should be used instead.
+pub fn verify_certificate_attestation(
+    api: &dyn Api,
+    cert: &CertificateInfo,
+    recipient_id: &str,
+) -> StdResult<CanonicalAddr> {
+    let attestation = cert
+        .attestation
+        .as_ref()
+        .ok_or_else(|| StdError::generic_err("certificate has no attestation"))?;
+    if !attestation_is_valid(api, attestation, cert, recipient_id)? {
+        return Err(StdError::generic_err(
+            "certificate attestation signature is invalid",
+        ));
+    }
+    Ok(address_from_secp256k1_pubkey(attestation.public_key.as_slice()))
+}
+
+/// non-failing variant used by the `VerifyCertificate` query, which reports `verified:
+/// false` rather than erroring so wallets/explorers can render a trust badge either way
+pub fn verify_certificate_attestation_unchecked(
+    api: &dyn Api,
+    cert: &CertificateInfo,
+    recipient_id: &str,
+) -> StdResult<CertificateVerification> {
+    match &cert.attestation {
+        Some(attestation) => {
+            let verified =
+                attestation_is_valid(api, attestation, cert, recipient_id).unwrap_or(false);
+            Ok(CertificateVerification {
+                verified,
+                issuer_address: address_from_secp256k1_pubkey(attestation.public_key.as_slice()),
+            })
+        }
+        None => Ok(CertificateVerification {
+            verified: false,
+            issuer_address: CanonicalAddr::from(vec![]),
+        }),
+    }
+}
+
+fn attestation_is_valid(
+    api: &dyn Api,
+    attestation: &Attestation,
+    cert: &CertificateInfo,
+    recipient_id: &str,
+) -> StdResult<bool> {
+    match attestation.scheme.clone().unwrap_or_default() {
+        AttestationScheme::Secp256k1 => {
+            let hash = certificate_message_hash(cert, recipient_id);
+            api.secp256k1_verify(
+                &hash,
+                attestation.signature.as_slice(),
+                attestation.public_key.as_slice(),
+            )
+            .map_err(|err| StdError::generic_err(format!("attestation verification error: {}", err)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_message_is_length_prefixed_in_fixed_order() {
+        let cert = CertificateInfo {
+            name: Some("Diploma".to_string()),
+            cert_type: None,
+            issue_date: None,
+            expire_date: None,
+            cert_number: "C-1".to_string(),
+            issuer_id: Some("registrar".to_string()),
+            attestation: None,
+        };
+        let mut expected: Vec<u8> = Vec::new();
+        for field in [
+            cert.cert_number.as_str(),
+            "Diploma",
+            "",
+            "",
+            "registrar",
+            "recipient-1",
+        ] {
+            expected.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            expected.extend_from_slice(field.as_bytes());
+        }
+        assert_eq!(canonical_certificate_message(&cert, "recipient-1"), expected);
+    }
+
+    #[test]
+    fn canonical_message_differs_by_recipient() {
+        let cert = CertificateInfo {
+            name: None,
+            cert_type: None,
+            issue_date: None,
+            expire_date: None,
+            cert_number: "C-1".to_string(),
+            issuer_id: None,
+            attestation: None,
+        };
+        assert_ne!(
+            canonical_certificate_message(&cert, "alice"),
+            canonical_certificate_message(&cert, "bob"),
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! execute/query handler logic that wires the metadata validation, certificate
+//! attestation, and metadata attestation subsystems into the places a real SNIP-721
+//! contract would call them from: minting a token, updating its metadata, and answering
+//! the `VerifyCertificate`/`GetAttestations`/`VerifyAttestation` queries.
+//!
+//! This module intentionally stops at the handler layer -- it does not itself define
+//! `ExecuteMsg`/`QueryMsg`/`InstantiateMsg` or the rest of the SNIP-721 surface (transfer,
+//! approve, base minting), since none of that exists in this tree. Each function here is
+//! the piece a full contract's `execute`/`query`/`instantiate` entry points would delegate
+//! to for the behavior described in the metadata validation, certificate attestation, and
+//! metadata attestation requests.
+
+use cosmwasm_std::{Api, Binary, StdError, StdResult, Storage};
+use thiserror::Error;
+
+#[cfg(feature = "certificate")]
+use crate::certificate_verification::{
+    verify_certificate_attestation, verify_certificate_attestation_unchecked, CertificateVerification,
+};
+use crate::crypto_utils::address_from_secp256k1_pubkey;
+use crate::metadata_attestation::{
+    attestation_message_hash, verify_attestations_against_pubkeys, visible_attestations,
+    AttestationEndorsement, MetadataAttestation,
+};
+use crate::metadata_validation::{MetadataLimits, MetadataValidationError};
+use crate::state;
+#[cfg(feature = "certificate")]
+use crate::token::CertificateInfo;
+use crate::token::Metadata;
+
+/// errors a real `execute`/`query` entry point would turn 1:1 into `ContractError`
+/// variants. Kept as a thin wrapper around `MetadataValidationError` (rather than
+/// collapsing it into `StdError::generic_err`) so callers can match on
+/// `MetadataTooLarge`/`InvalidMetadata` instead of parsing an error string.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(String),
+    #[error(transparent)]
+    Metadata(#[from] MetadataValidationError),
+}
+
+impl From<StdError> for ContractError {
+    fn from(err: StdError) -> Self {
+        ContractError::Std(err.to_string())
+    }
+}
+
+/// sets the `MetadataLimits` a deployer chose at instantiation. Called once from
+/// `instantiate`, surfacing the limits as contract config rather than a hard-coded default
+pub fn instantiate_metadata_limits(storage: &mut dyn Storage, limits: &MetadataLimits) -> StdResult<()> {
+    state::metadata_limits(storage).save(limits)
+}
+
+/// validates `metadata` against the configured `MetadataLimits`, falling back to
+/// `MetadataLimits::default()` if `instantiate_metadata_limits` was never called. Called on
+/// mint and on every metadata update; rejects the call outright with a typed
+/// `ContractError` (mirroring how a real `execute` handler would match on
+/// `MetadataTooLarge`/`InvalidMetadata`) rather than silently accepting oversized or
+/// malformed metadata.
+pub fn validate_metadata_for_mint(storage: &dyn Storage, metadata: &Metadata) -> Result<(), ContractError> {
+    let limits = state::metadata_limits_read(storage)
+        .may_load()?
+        .unwrap_or_default();
+    metadata.validate(&limits)?;
+    Ok(())
+}
+
+/// verifies `certificate`'s attestation and checks that the recovered issuer address is
+/// enrolled and enabled in the issuer registry, rejecting the mint/update (mirroring how a
+/// real `execute` handler would bubble this up as a `ContractError`) if either check fails.
+/// Called on mint and on metadata update whenever the certificate carries an attestation.
+#[cfg(feature = "certificate")]
+pub fn verify_certificate_for_mint(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    certificate: &CertificateInfo,
+    recipient_id: &str,
+) -> StdResult<()> {
+    let issuer_address = verify_certificate_attestation(api, certificate, recipient_id)?;
+    let issuer = state::issuers_read(storage)
+        .may_load(issuer_address.as_slice())?
+        .ok_or_else(|| StdError::generic_err("certificate issuer is not registered"))?;
+    if !issuer.enabled {
+        return Err(StdError::generic_err("certificate issuer is disabled"));
+    }
+    Ok(())
+}
+
+/// answers the `VerifyCertificate` query: reports whether `certificate`'s attestation is
+/// valid and which address it recovers to, without rejecting on failure the way
+/// `verify_certificate_for_mint` does for mint/update
+#[cfg(feature = "certificate")]
+pub fn query_verify_certificate(
+    api: &dyn Api,
+    certificate: &CertificateInfo,
+    recipient_id: &str,
+) -> StdResult<CertificateVerification> {
+    verify_certificate_attestation_unchecked(api, certificate, recipient_id)
+}
+
+/// handles `AddMetadataAttestation`: verifies that `signature` is a valid secp256k1
+/// signature over `attestation_message_hash(token_id, field_path, value)` by `public_key`,
+/// derives `signer` from that same `public_key` (never trusting a caller-asserted
+/// address), and replaces any existing attestation from `signer` for this `(token_id,
+/// field_path)` pair with the new one, per the "replaceable per (signer, token_id,
+/// field_path)" rule the request calls for. Rejects the call if the signature doesn't
+/// verify, so a caller can never evict a legitimate attestation with unverifiable junk.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_add_metadata_attestation(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    public_key: Binary,
+    token_id: &str,
+    field_path: &str,
+    value: String,
+    signature: Binary,
+    private: bool,
+) -> StdResult<()> {
+    let hash = attestation_message_hash(token_id, field_path, &value);
+    let verified = api
+        .secp256k1_verify(&hash, signature.as_slice(), public_key.as_slice())
+        .map_err(|err| StdError::generic_err(format!("attestation verification error: {}", err)))?;
+    if !verified {
+        return Err(StdError::generic_err("attestation signature is invalid"));
+    }
+    let signer = address_from_secp256k1_pubkey(public_key.as_slice());
+
+    let mut entries = state::load_attestations(storage, token_id, field_path);
+    entries.retain(|entry| entry.signer != signer);
+    entries.push(MetadataAttestation {
+        signer,
+        signature,
+        field_path: field_path.to_string(),
+        value,
+        private,
+    });
+    state::save_attestations(storage, token_id, field_path, &entries)
+}
+
+/// handles `GetAttestations { token_id }`: returns every attestation for `token_id` across
+/// all field paths that `viewer_has_access` is allowed to see. Since attestations are
+/// stored per `(token_id, field_path)`, callers must pass every `field_path` that has ever
+/// been attested for this token (a full contract would track that set alongside the
+/// token); this function does the filtering once that list is known.
+pub fn query_get_attestations(
+    storage: &dyn Storage,
+    token_id: &str,
+    field_paths: &[String],
+    viewer_has_access: bool,
+) -> Vec<MetadataAttestation> {
+    let all: Vec<MetadataAttestation> = field_paths
+        .iter()
+        .flat_map(|field_path| state::load_attestations(storage, token_id, field_path))
+        .collect();
+    visible_attestations(&all, viewer_has_access)
+}
+
+/// handles `VerifyAttestation { token_id, field_path, against_pubkeys }`: re-runs
+/// signature verification for `value` against every caller-supplied candidate key,
+/// restricted to the attestations the caller is allowed to see
+pub fn query_verify_attestation(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    token_id: &str,
+    field_path: &str,
+    value: &str,
+    against_pubkeys: &[Binary],
+    viewer_has_access: bool,
+) -> StdResult<Vec<AttestationEndorsement>> {
+    let stored = state::load_attestations(storage, token_id, field_path);
+    let visible = visible_attestations(&stored, viewer_has_access);
+    verify_attestations_against_pubkeys(api, &visible, token_id, field_path, value, against_pubkeys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{MockApi, MockStorage};
+
+    #[test]
+    fn execute_add_metadata_attestation_rejects_unverifiable_signature() {
+        let mut storage = MockStorage::new();
+        let api = MockApi::default();
+
+        let result = execute_add_metadata_attestation(
+            &mut storage,
+            &api,
+            Binary::from(vec![0u8; 33]),
+            "token-1",
+            "attributes[graduation_year]",
+            "2026".to_string(),
+            Binary::from(vec![0u8; 64]),
+            false,
+        );
+
+        assert!(result.is_err());
+        assert!(state::load_attestations(&storage, "token-1", "attributes[graduation_year]").is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! small shared helpers for features that verify secp256k1 signatures against addresses
+//! stored in contract state (issuer attestations, metadata attestations).  Kept separate
+//! from any one feature's module so it isn't accidentally gated behind a Cargo feature it
+//! doesn't belong to.
+
+use cosmwasm_std::CanonicalAddr;
+use sha2::{Digest, Sha256};
+
+/// standard cosmos address derivation from a compressed secp256k1 public key:
+/// `ripemd160(sha256(pubkey))`
+pub fn address_from_secp256k1_pubkey(public_key: &[u8]) -> CanonicalAddr {
+    use ripemd::Ripemd160;
+
+    let sha = Sha256::digest(public_key);
+    let hash = Ripemd160::digest(sha);
+    CanonicalAddr::from(hash.as_slice())
+}
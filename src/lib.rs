@@ -0,0 +1,8 @@
+pub mod token;
+#[cfg(feature = "certificate")]
+pub mod certificate_verification;
+pub mod contract;
+pub mod crypto_utils;
+pub mod metadata_attestation;
+pub mod metadata_validation;
+pub mod state;
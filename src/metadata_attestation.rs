@@ -0,0 +1,136 @@
+//! per-field metadata attestations, letting a third party endorse a specific
+//! `(token_id, field_path, value)` triple without the holder having to trust the minter's
+//! whole `Extension`.  Unlike `certificate_verification`, this does not require the
+//! `certificate` feature -- any metadata field (a `Trait`, a recipient `id`, ...) can be
+//! attested.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Api, Binary, CanonicalAddr, StdResult};
+
+use sha2::{Digest, Sha256};
+
+use crate::crypto_utils::address_from_secp256k1_pubkey;
+
+/// one signer's endorsement that `value` is the correct value for `field_path` on
+/// `token_id`.  Replaceable per `(signer, token_id, field_path)`: submitting a new
+/// `AddMetadataAttestation` for the same triple overwrites the stored attestation rather
+/// than appending another one, so the list stored per `(token_id, field_path)` holds at
+/// most one attestation per `signer`
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct MetadataAttestation {
+    /// address of the party that produced `signature`
+    pub signer: CanonicalAddr,
+    /// signature over `canonical_attestation_message(token_id, field_path, value)`
+    pub signature: Binary,
+    /// dotted path identifying the attested field, e.g. `"attributes[graduation_year]"`
+    /// or `"certified_individual.id"`
+    pub field_path: String,
+    /// the value being endorsed
+    pub value: String,
+    /// if true, only returned to viewers holding the token's viewing key/permit
+    pub private: bool,
+}
+
+/// Builds the canonical byte message a signer produces to attest a metadata field.
+///
+/// Fields are concatenated in this fixed order, each preceded by its length as a 4-byte
+/// little-endian `u32`: `token_id`, `field_path`, `value`.  `token_id` is included so an
+/// attestation produced for one token can never be replayed against another.
+pub fn canonical_attestation_message(token_id: &str, field_path: &str, value: &str) -> Vec<u8> {
+    let mut msg: Vec<u8> = Vec::new();
+    for field in [token_id, field_path, value] {
+        let bytes = field.as_bytes();
+        msg.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        msg.extend_from_slice(bytes);
+    }
+    msg
+}
+
+/// sha256 digest of the canonical attestation message, which is what is actually signed
+pub fn attestation_message_hash(token_id: &str, field_path: &str, value: &str) -> Vec<u8> {
+    Sha256::digest(canonical_attestation_message(token_id, field_path, value)).to_vec()
+}
+
+/// result of checking one candidate key against the attestations stored for a
+/// `(token_id, field_path)`, as returned by the `VerifyAttestation` query
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct AttestationEndorsement {
+    /// one of the caller-supplied `against_pubkeys`
+    pub public_key: Binary,
+    /// true if this key's address has a stored attestation for `value` whose signature
+    /// verifies
+    pub endorsed: bool,
+}
+
+/// Re-runs `secp256k1_verify` for `value` at `field_path` on `token_id` against every key
+/// in `against_pubkeys`, reporting which of them endorsed it.  `attestations` should
+/// already be filtered to the ones visible to the current viewer (see
+/// `visible_attestations`) and to `field_path`.
+pub fn verify_attestations_against_pubkeys(
+    api: &dyn Api,
+    attestations: &[MetadataAttestation],
+    token_id: &str,
+    field_path: &str,
+    value: &str,
+    against_pubkeys: &[Binary],
+) -> StdResult<Vec<AttestationEndorsement>> {
+    let hash = attestation_message_hash(token_id, field_path, value);
+    let mut results = Vec::with_capacity(against_pubkeys.len());
+    for public_key in against_pubkeys {
+        let signer = address_from_secp256k1_pubkey(public_key.as_slice());
+        let endorsed = attestations
+            .iter()
+            .filter(|a| a.field_path == field_path && a.value == value && a.signer == signer)
+            .any(|a| {
+                api.secp256k1_verify(&hash, a.signature.as_slice(), public_key.as_slice())
+                    .unwrap_or(false)
+            });
+        results.push(AttestationEndorsement {
+            public_key: public_key.clone(),
+            endorsed,
+        });
+    }
+    Ok(results)
+}
+
+/// filters `attestations` down to the ones a viewer is allowed to see: public attestations
+/// always pass, private ones only if `viewer_has_access` (derived by the caller from the
+/// token's viewing key/permit check before calling into this module)
+pub fn visible_attestations(
+    attestations: &[MetadataAttestation],
+    viewer_has_access: bool,
+) -> Vec<MetadataAttestation> {
+    attestations
+        .iter()
+        .filter(|a| !a.private || viewer_has_access)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_message_is_length_prefixed_in_fixed_order() {
+        let mut expected: Vec<u8> = Vec::new();
+        for field in ["token-1", "attributes[graduation_year]", "2026"] {
+            expected.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            expected.extend_from_slice(field.as_bytes());
+        }
+        assert_eq!(
+            canonical_attestation_message("token-1", "attributes[graduation_year]", "2026"),
+            expected
+        );
+    }
+
+    #[test]
+    fn canonical_message_differs_by_token_id() {
+        assert_ne!(
+            canonical_attestation_message("token-1", "field", "value"),
+            canonical_attestation_message("token-2", "field", "value"),
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(feature = "certificate")]
+use crate::token::CertificateInfo;
+use crate::token::{Extension, MediaFile, Metadata, Trait};
+
+/// configurable limits enforced by `Extension::validate`.  Exposed as contract
+/// instantiation config so deployers can tune them to their own storage/gas budget rather
+/// than being stuck with one hard-coded set of caps
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct MetadataLimits {
+    /// max length for `name`
+    pub max_name_len: u32,
+    /// max length for `description`
+    pub max_description_len: u32,
+    /// max length for `external_url`
+    pub max_external_url_len: u32,
+    /// max length for `token_uri`
+    pub max_token_uri_len: u32,
+    /// max length for a `Trait`'s `trait_type` or `value`
+    pub max_trait_field_len: u32,
+    /// max number of entries in `attributes`
+    pub max_attributes: u32,
+    /// max number of entries in `media`
+    pub max_media: u32,
+    /// max number of entries in `certified_items`
+    pub max_certified_items: u32,
+    /// max number of entries in `issuing_organizations`
+    pub max_issuing_organizations: u32,
+}
+
+impl Default for MetadataLimits {
+    fn default() -> Self {
+        MetadataLimits {
+            max_name_len: 256,
+            max_description_len: 2048,
+            max_external_url_len: 512,
+            max_token_uri_len: 512,
+            max_trait_field_len: 256,
+            max_attributes: 64,
+            max_media: 32,
+            max_certified_items: 32,
+            max_issuing_organizations: 16,
+        }
+    }
+}
+
+/// URL schemes metadata is allowed to use for `token_uri`, `external_url`, `image`,
+/// `animation_url`, and media `url` fields
+const ALLOWED_URL_SCHEMES: [&str; 4] = ["http://", "https://", "ipfs://", "ar://"];
+
+/// errors raised by `Extension::validate`.  At the contract layer these are wrapped
+/// unchanged in `ContractError::Metadata`
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum MetadataValidationError {
+    #[error("{field} must not exceed {max} {unit}")]
+    MetadataTooLarge {
+        field: String,
+        max: u32,
+        unit: &'static str,
+    },
+    #[error("{field} is invalid: {reason}")]
+    InvalidMetadata { field: String, reason: String },
+}
+
+fn check_len(
+    field: &str,
+    value: &str,
+    max: u32,
+) -> Result<(), MetadataValidationError> {
+    if value.chars().count() as u32 > max {
+        return Err(MetadataValidationError::MetadataTooLarge {
+            field: field.to_string(),
+            max,
+            unit: "characters",
+        });
+    }
+    Ok(())
+}
+
+fn check_count(
+    field: &str,
+    count: usize,
+    max: u32,
+) -> Result<(), MetadataValidationError> {
+    if count as u32 > max {
+        return Err(MetadataValidationError::MetadataTooLarge {
+            field: field.to_string(),
+            max,
+            unit: "entries",
+        });
+    }
+    Ok(())
+}
+
+fn check_url(field: &str, url: &str) -> Result<(), MetadataValidationError> {
+    if ALLOWED_URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme)) {
+        Ok(())
+    } else {
+        Err(MetadataValidationError::InvalidMetadata {
+            field: field.to_string(),
+            reason: format!(
+                "must start with one of {}",
+                ALLOWED_URL_SCHEMES.join(", ")
+            ),
+        })
+    }
+}
+
+impl Trait {
+    /// validates `trait_type` and `value` against `limits`
+    pub fn validate(&self, limits: &MetadataLimits) -> Result<(), MetadataValidationError> {
+        check_len("attributes[].value", &self.value, limits.max_trait_field_len)?;
+        if let Some(trait_type) = &self.trait_type {
+            check_len("attributes[].trait_type", trait_type, limits.max_trait_field_len)?;
+        }
+        Ok(())
+    }
+}
+
+impl MediaFile {
+    /// validates `url`'s scheme and the digest checks from `validate_content_hash`
+    pub fn validate(&self) -> Result<(), MetadataValidationError> {
+        let url = match &self.source {
+            crate::token::MediaSource::Plain { url } => url,
+            crate::token::MediaSource::Encrypted { url, .. } => url,
+        };
+        check_url("media[].url", url)?;
+        self.validate_content_hash().map_err(|err| {
+            MetadataValidationError::InvalidMetadata {
+                field: "media[].content_hash".to_string(),
+                reason: err.to_string(),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "certificate")]
+impl CertificateInfo {
+    /// validates `cert_number`, the only required certificate field with no natural cap
+    /// otherwise
+    pub fn validate(&self, limits: &MetadataLimits) -> Result<(), MetadataValidationError> {
+        check_len("certificate.cert_number", &self.cert_number, limits.max_name_len)
+    }
+}
+
+impl Metadata {
+    /// validates `token_uri` (if set) and delegates to `Extension::validate` for
+    /// `extension` (if set).  Called on mint and on metadata update, same as
+    /// `Extension::validate`
+    pub fn validate(&self, limits: &MetadataLimits) -> Result<(), MetadataValidationError> {
+        if let Some(token_uri) = &self.token_uri {
+            check_len("token_uri", token_uri, limits.max_token_uri_len)?;
+            check_url("token_uri", token_uri)?;
+        }
+        if let Some(extension) = &self.extension {
+            extension.validate(limits)?;
+        }
+        Ok(())
+    }
+}
+
+impl Extension {
+    /// validates every size- and shape-bounded field of the metadata, enforcing `limits`.
+    /// Called on mint and on metadata update so a single token can never bloat state with
+    /// arbitrarily large strings or vectors
+    pub fn validate(&self, limits: &MetadataLimits) -> Result<(), MetadataValidationError> {
+        #[cfg(feature = "certificate")]
+        self.certificate.validate(limits)?;
+
+        if let Some(name) = &self.name {
+            check_len("name", name, limits.max_name_len)?;
+        }
+        if let Some(description) = &self.description {
+            check_len("description", description, limits.max_description_len)?;
+        }
+        if let Some(external_url) = &self.external_url {
+            check_len("external_url", external_url, limits.max_external_url_len)?;
+            check_url("external_url", external_url)?;
+        }
+        if let Some(image) = &self.image {
+            check_url("image", image)?;
+        }
+        if let Some(animation_url) = &self.animation_url {
+            check_url("animation_url", animation_url)?;
+        }
+        if let Some(background_color) = &self.background_color {
+            if !is_six_char_hex(background_color) {
+                return Err(MetadataValidationError::InvalidMetadata {
+                    field: "background_color".to_string(),
+                    reason: "must be six hexadecimal characters without a leading #".to_string(),
+                });
+            }
+        }
+        if let Some(attributes) = &self.attributes {
+            check_count("attributes", attributes.len(), limits.max_attributes)?;
+            for attribute in attributes {
+                attribute.validate(limits)?;
+            }
+        }
+        if let Some(media) = &self.media {
+            check_count("media", media.len(), limits.max_media)?;
+            for file in media {
+                file.validate()?;
+            }
+        }
+        #[cfg(feature = "certificate")]
+        if let Some(certified_items) = &self.certified_items {
+            check_count("certified_items", certified_items.len(), limits.max_certified_items)?;
+        }
+        #[cfg(feature = "certificate")]
+        if let Some(issuing_organizations) = &self.issuing_organizations {
+            check_count(
+                "issuing_organizations",
+                issuing_organizations.len(),
+                limits.max_issuing_organizations,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn is_six_char_hex(value: &str) -> bool {
+    value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_at_limit_passes() {
+        let limits = MetadataLimits::default();
+        let extension = Extension {
+            name: Some("x".repeat(limits.max_name_len as usize)),
+            ..Default::default()
+        };
+        assert_eq!(extension.validate(&limits), Ok(()));
+    }
+
+    #[test]
+    fn name_over_limit_fails() {
+        let limits = MetadataLimits::default();
+        let extension = Extension {
+            name: Some("x".repeat(limits.max_name_len as usize + 1)),
+            ..Default::default()
+        };
+        assert_eq!(
+            extension.validate(&limits),
+            Err(MetadataValidationError::MetadataTooLarge {
+                field: "name".to_string(),
+                max: limits.max_name_len,
+                unit: "characters",
+            })
+        );
+    }
+
+    #[test]
+    fn background_color_rejects_leading_hash() {
+        let limits = MetadataLimits::default();
+        let extension = Extension {
+            background_color: Some("#ffffff".to_string()),
+            ..Default::default()
+        };
+        assert!(extension.validate(&limits).is_err());
+    }
+
+    #[test]
+    fn background_color_accepts_six_hex_chars() {
+        let limits = MetadataLimits::default();
+        let extension = Extension {
+            background_color: Some("ffffff".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(extension.validate(&limits), Ok(()));
+    }
+
+    #[test]
+    fn image_rejects_disallowed_scheme() {
+        let limits = MetadataLimits::default();
+        let extension = Extension {
+            image: Some("ftp://example.com/a.png".to_string()),
+            ..Default::default()
+        };
+        assert!(extension.validate(&limits).is_err());
+    }
+
+    #[test]
+    fn token_uri_at_limit_passes() {
+        let limits = MetadataLimits::default();
+        let metadata = Metadata {
+            token_uri: Some(format!(
+                "https://{}",
+                "x".repeat(limits.max_token_uri_len as usize - "https://".len())
+            )),
+            extension: None,
+        };
+        assert_eq!(metadata.validate(&limits), Ok(()));
+    }
+
+    #[test]
+    fn token_uri_over_limit_fails() {
+        let limits = MetadataLimits::default();
+        let metadata = Metadata {
+            token_uri: Some(format!(
+                "https://{}",
+                "x".repeat(limits.max_token_uri_len as usize - "https://".len() + 1)
+            )),
+            extension: None,
+        };
+        assert!(metadata.validate(&limits).is_err());
+    }
+
+    #[test]
+    fn token_uri_rejects_disallowed_scheme() {
+        let limits = MetadataLimits::default();
+        let metadata = Metadata {
+            token_uri: Some("ftp://example.com/metadata.json".to_string()),
+            extension: None,
+        };
+        assert!(metadata.validate(&limits).is_err());
+    }
+
+    #[test]
+    fn token_uri_allows_ipfs_scheme() {
+        let limits = MetadataLimits::default();
+        let metadata = Metadata {
+            token_uri: Some("ipfs://bafybeigd.../metadata.json".to_string()),
+            extension: None,
+        };
+        assert_eq!(metadata.validate(&limits), Ok(()));
+    }
+
+    #[test]
+    fn metadata_with_no_fields_set_passes() {
+        let limits = MetadataLimits::default();
+        let metadata = Metadata::default();
+        assert_eq!(metadata.validate(&limits), Ok(()));
+    }
+}
@@ -0,0 +1,142 @@
+//! contract storage: per-token permission grants (referenced by `Token`), the certificate
+//! issuer registry, metadata validation limits, and per-field metadata attestations
+//!
+//! Uses `cosmwasm_storage`'s `bucket`/`singleton` helpers, matching the rest of the
+//! reference impl's storage layout rather than migrating to `cw-storage-plus`.
+#![allow(deprecated)]
+
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
+
+use crate::metadata_attestation::MetadataAttestation;
+use crate::metadata_validation::MetadataLimits;
+#[cfg(feature = "certificate")]
+use crate::token::IssuerInfo;
+
+/// one address's standing grant against a single token, as referenced by
+/// `Token::permissions`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Permission {
+    /// address this permission was granted to
+    pub address: CanonicalAddr,
+    /// may view the owner despite owner-privacy settings
+    pub view_owner: bool,
+    /// may view private metadata
+    pub view_metadata: bool,
+    /// may transfer the token
+    pub transfer: bool,
+}
+
+/// same grant shape as `Permission`, but held by a querying contract's code hash rather
+/// than an address, as referenced by `Token::code_permissions`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CodePermission {
+    /// code hash this permission was granted to
+    pub code_hash: String,
+    /// may view the owner despite owner-privacy settings
+    pub view_owner: bool,
+    /// may view private metadata
+    pub view_metadata: bool,
+    /// may transfer the token
+    pub transfer: bool,
+}
+
+#[cfg(feature = "certificate")]
+const PREFIX_ISSUERS: &[u8] = b"issuers";
+
+/// registry of authorized certificate issuers, keyed by the issuer's canonical address.
+/// Consulted by the mint/update path so a certificate's attestation is only trusted if it
+/// recovers to an address enrolled here with `enabled: true`
+#[cfg(feature = "certificate")]
+pub fn issuers(storage: &mut dyn Storage) -> Bucket<'_, IssuerInfo> {
+    bucket(storage, PREFIX_ISSUERS)
+}
+
+#[cfg(feature = "certificate")]
+pub fn issuers_read(storage: &dyn Storage) -> ReadonlyBucket<'_, IssuerInfo> {
+    bucket_read(storage, PREFIX_ISSUERS)
+}
+
+const KEY_METADATA_LIMITS: &[u8] = b"metadata_limits";
+
+/// the `MetadataLimits` a deployer configured at instantiation, consulted by `validate()`
+/// calls on every mint and metadata update
+pub fn metadata_limits(storage: &mut dyn Storage) -> Singleton<'_, MetadataLimits> {
+    singleton(storage, KEY_METADATA_LIMITS)
+}
+
+pub fn metadata_limits_read(storage: &dyn Storage) -> ReadonlySingleton<'_, MetadataLimits> {
+    singleton_read(storage, KEY_METADATA_LIMITS)
+}
+
+const PREFIX_ATTESTATIONS: &[u8] = b"attestations";
+
+/// storage key for the attestations on one `(token_id, field_path)` pair. `token_id` is
+/// preceded by its length as a 4-byte little-endian `u32` (the same length-prefixing
+/// scheme used for the signed messages themselves) rather than joined to `field_path` with
+/// a separator byte -- both are caller-controlled UTF-8 strings and `U+0000` is legal
+/// UTF-8, so a NUL-byte separator could be forged by embedding one in either string, e.g.
+/// `("tokenA", "x\0certified_individual.id")` colliding with `("tokenA\0x",
+/// "certified_individual.id")`. A length prefix on `token_id` makes the split
+/// unambiguous regardless of what bytes either string contains.
+fn attestations_key(token_id: &str, field_path: &str) -> Vec<u8> {
+    let mut key = (token_id.len() as u32).to_le_bytes().to_vec();
+    key.extend_from_slice(token_id.as_bytes());
+    key.extend_from_slice(field_path.as_bytes());
+    key
+}
+
+fn attestations(storage: &mut dyn Storage) -> Bucket<'_, Vec<MetadataAttestation>> {
+    bucket(storage, PREFIX_ATTESTATIONS)
+}
+
+fn attestations_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Vec<MetadataAttestation>> {
+    bucket_read(storage, PREFIX_ATTESTATIONS)
+}
+
+/// loads the attestations stored for `(token_id, field_path)`, defaulting to empty if none
+/// have been submitted yet
+pub fn load_attestations(storage: &dyn Storage, token_id: &str, field_path: &str) -> Vec<MetadataAttestation> {
+    attestations_read(storage)
+        .load(&attestations_key(token_id, field_path))
+        .unwrap_or_default()
+}
+
+/// overwrites the attestation list stored for `(token_id, field_path)`
+pub fn save_attestations(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    field_path: &str,
+    entries: &[MetadataAttestation],
+) -> StdResult<()> {
+    attestations(storage).save(&attestations_key(token_id, field_path), &entries.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attestations_key_does_not_collide_across_a_shifted_split() {
+        let a = attestations_key("tokenA", "x\0certified_individual.id");
+        let b = attestations_key("tokenA\0x", "certified_individual.id");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn attestations_key_is_injective_over_token_id_and_field_path() {
+        assert_ne!(
+            attestations_key("token-1", "field"),
+            attestations_key("token-2", "field")
+        );
+        assert_ne!(
+            attestations_key("token-1", "field-a"),
+            attestations_key("token-1", "field-b")
+        );
+    }
+}
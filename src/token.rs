@@ -1,10 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::CanonicalAddr;
+use cosmwasm_std::{Binary, CanonicalAddr, StdError, StdResult};
 
 use crate::state::{Permission, CodePermission};
 
+/// length in bytes of a valid sha256 digest
+const SHA256_DIGEST_LEN: usize = 32;
+
 /// token
 #[derive(Serialize, Deserialize)]
 pub struct Token {
@@ -31,24 +34,44 @@ pub struct Metadata {
     pub extension: Option<Extension>,
 }
 
+/// identifies which optional field groups are compiled into `Extension`, so explorers and
+/// other consumers of the generated JSON schema can tell which profile a token uses
+/// without guessing from which fields happen to be populated
+pub const METADATA_STANDARD: &str = if cfg!(feature = "certificate") {
+    "generic+certificate"
+} else {
+    "generic"
+};
+
 /// metadata extension
 /// You can add any metadata fields you need here.  These fields are based on
 /// https://docs.opensea.io/docs/metadata-standards and are the metadata fields that
 /// Stashh uses for robust NFT display.  Urls should be prefixed with `http://`, `https://`, `ipfs://`, or
 /// `ar://`
+///
+/// The certificate-credential fields below are gated behind the `certificate` Cargo
+/// feature (off by default) so a deployer who only wants a plain OpenSea-style collectible
+/// doesn't pay for storage or schema noise they'll never use.  Enable it in `Cargo.toml`
+/// with `snip721-reference-impl = { features = ["certificate"] }` to mint credentials.
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct Extension {
     /// certificate information
+    #[cfg(feature = "certificate")]
     pub certificate: CertificateInfo,
     /// certificate recipient (if certified is human)
+    #[cfg(feature = "certificate")]
     pub certified_individual: Option<RecipientInfo>,
     /// certified items
+    #[cfg(feature = "certificate")]
     pub certified_items: Option<Vec<ItemInfo>>,
     /// optional list of organizations issuing the certificate
+    #[cfg(feature = "certificate")]
     pub issuing_organizations: Option<Vec<Organization>>,
     /// optional list of individuals issuing the certificate
+    #[cfg(feature = "certificate")]
     pub issuing_individuals: Option<Vec<Individual>>,
     /// optional list of additional information for the certificate. Courses, instructors, etc.
+    #[cfg(feature = "certificate")]
     pub additions: Option<Vec<Addition>>,
     /// url to the image
     pub image: Option<String>,
@@ -101,13 +124,214 @@ pub struct MediaFile {
     pub file_type: Option<String>,
     /// file extension
     pub extension: Option<String>,
-    /// authentication information
-    pub authentication: Option<Authentication>,
-    /// url to the file.  Urls should be prefixed with `http://`, `https://`, `ipfs://`, or `ar://`
-    pub url: String,
+    /// where to fetch the file from, and whether it is encrypted.  Deserializes legacy
+    /// `{ url, authentication }` payloads transparently, see `MediaSource`
+    #[serde(flatten)]
+    pub source: MediaSource,
+    /// digest of the plaintext asset `source`'s url points to (after decryption, if
+    /// encrypted), letting a holder fetch the bytes and confirm they are the ones the
+    /// minter intended.  Must be a well-formed 32-byte digest when present.  This is
+    /// distinct from `MediaSource::Encrypted::ciphertext_hash`, which fingerprints the
+    /// ciphertext that comes back over the wire before it is ever decrypted.  The two
+    /// fields are named differently (rather than both `content_hash`) because both flatten
+    /// into the same `MediaFile` JSON object and a shared name would collide
+    pub content_hash: Option<Binary>,
+    /// algorithm used to produce `content_hash`, defaults to sha256
+    pub hash_algorithm: Option<HashAlgorithm>,
+    /// display dimensions/size of the asset, so clients can lay out media without
+    /// fetching it first
+    pub image_info: Option<ImageInfo>,
+}
+
+impl MediaFile {
+    /// checks that `content_hash`, if present, is a well-formed digest for its
+    /// `hash_algorithm`.  Called at mint and metadata update time
+    pub fn validate_content_hash(&self) -> StdResult<()> {
+        if let Some(hash) = &self.content_hash {
+            match self.hash_algorithm.clone().unwrap_or_default() {
+                HashAlgorithm::Sha256 => {
+                    if hash.len() != SHA256_DIGEST_LEN {
+                        return Err(StdError::generic_err(format!(
+                            "content_hash must be a {}-byte sha256 digest",
+                            SHA256_DIGEST_LEN
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// where a `MediaFile`'s bytes live and whether a decryption key is required to read them.
+/// Replaces the ambiguous `authentication` field, which overloaded one struct to mean both
+/// basic-auth credentials and a decryption key.  `Serialize` and `Deserialize` are both
+/// implemented by hand (see below) in terms of `MediaSourceRaw`, an untagged shape whose
+/// fields merge directly into `MediaFile` via `#[serde(flatten)]`.  A derived, externally
+/// tagged `Serialize` would not round-trip here: flattening a tagged enum produces
+/// `{"plain":{"url":"..."}}` nested under `MediaFile`'s other fields, which the custom
+/// `Deserialize` (expecting bare `url`/`key`/`iv`/...) cannot read back
+#[derive(Clone, PartialEq, Debug)]
+pub enum MediaSource {
+    /// file is served as-is, no decryption required
+    Plain { url: String },
+    /// file is encrypted at `url` and must be decrypted with `key`/`iv`/`algorithm` after
+    /// download
+    Encrypted {
+        url: String,
+        /// decryption key for the file at `url`
+        key: Binary,
+        /// initialization vector used with `key`
+        iv: Binary,
+        /// cipher used to encrypt the file, e.g. "AES-CTR"
+        algorithm: Option<String>,
+        /// digest of the *encrypted* bytes at `url`, so a client can verify the download
+        /// before attempting to decrypt it.  Named distinctly from `MediaFile::content_hash`
+        /// (the plaintext digest) since both flatten into the same JSON object
+        ciphertext_hash: Option<Binary>,
+    },
 }
 
-/// media file authentication
+impl Default for MediaSource {
+    fn default() -> Self {
+        MediaSource::Plain {
+            url: String::new(),
+        }
+    }
+}
+
+/// the untagged wire shape both `Serialize` and `Deserialize` for `MediaSource` go
+/// through, so that writing a value and reading it back always agree.  Also accepts the
+/// legacy `{ url, authentication }` encoding on the way in.  Presence of any of
+/// `key`/`iv`/`algorithm` selects the `Encrypted` variant; otherwise a legacy
+/// `authentication.key` is treated as a decryption key only when `authentication.user` is
+/// absent (see `MediaSource::deserialize` for why), and anything else falls back to
+/// `Plain`
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct MediaSourceRaw {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<Binary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iv: Option<Binary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    algorithm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ciphertext_hash: Option<Binary>,
+    /// legacy field, kept only so old payloads keep deserializing; never written out by
+    /// `Serialize`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authentication: Option<Authentication>,
+}
+
+impl From<&MediaSource> for MediaSourceRaw {
+    fn from(source: &MediaSource) -> Self {
+        match source {
+            MediaSource::Plain { url } => MediaSourceRaw {
+                url: url.clone(),
+                key: None,
+                iv: None,
+                algorithm: None,
+                ciphertext_hash: None,
+                authentication: None,
+            },
+            MediaSource::Encrypted {
+                url,
+                key,
+                iv,
+                algorithm,
+                ciphertext_hash,
+            } => MediaSourceRaw {
+                url: url.clone(),
+                key: Some(key.clone()),
+                iv: Some(iv.clone()),
+                algorithm: algorithm.clone(),
+                ciphertext_hash: ciphertext_hash.clone(),
+                authentication: None,
+            },
+        }
+    }
+}
+
+impl Serialize for MediaSource {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        MediaSourceRaw::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaSource {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = MediaSourceRaw::deserialize(deserializer)?;
+        if raw.key.is_some() || raw.iv.is_some() || raw.algorithm.is_some() {
+            return Ok(MediaSource::Encrypted {
+                url: raw.url,
+                key: raw.key.unwrap_or_default(),
+                iv: raw.iv.unwrap_or_default(),
+                algorithm: raw.algorithm,
+                ciphertext_hash: raw.ciphertext_hash,
+            });
+        }
+        // `Authentication` was ambiguous on purpose: `key` alone meant a decryption key,
+        // but `user` + `key` meant HTTP basic-auth credentials.  MediaSource has no slot
+        // for a basic-auth username, so a legacy entry that set `user` is treated as
+        // `Plain` rather than guessed into `Encrypted` with a decryption key that would
+        // actually be a password.  Only a bare `key` (no `user`) is migrated forward.
+        if let Some(auth) = raw.authentication {
+            if auth.user.is_none() {
+                if let Some(legacy_key) = auth.key {
+                    return Ok(MediaSource::Encrypted {
+                        url: raw.url,
+                        key: Binary::from(legacy_key.into_bytes()),
+                        iv: Binary::default(),
+                        algorithm: None,
+                        ciphertext_hash: raw.ciphertext_hash,
+                    });
+                }
+            }
+        }
+        Ok(MediaSource::Plain { url: raw.url })
+    }
+}
+
+impl JsonSchema for MediaSource {
+    fn schema_name() -> String {
+        "MediaSource".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        MediaSourceRaw::json_schema(gen)
+    }
+}
+
+/// digest algorithm used for `MediaFile::content_hash`
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+}
+
+/// display metadata for a media asset that a client can use to lay out a page without
+/// fetching the asset first
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub struct ImageInfo {
+    /// width of the asset in pixels
+    pub width: Option<u32>,
+    /// height of the asset in pixels
+    pub height: Option<u32>,
+    /// MIME type of the asset, e.g. "image/png"
+    pub mimetype: Option<String>,
+    /// size of the asset in bytes
+    pub size: Option<u64>,
+}
+
+/// legacy media file authentication, superseded by `MediaSource`.  Kept only so that old
+/// `{ url, authentication }` payloads still deserialize; new code should use `MediaSource`
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct Authentication {
     /// either a decryption key for encrypted files or a password for basic authentication
@@ -117,6 +341,7 @@ pub struct Authentication {
 }
 
 // certificate information
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct CertificateInfo {
     pub name: Option<String>,
@@ -125,9 +350,47 @@ pub struct CertificateInfo {
     pub expire_date: Option<String>,
     pub cert_number: String,
     pub issuer_id: Option<String>,
+    /// cryptographic proof binding this certificate to a verifiable issuer.  See
+    /// `certificate_verification` for how the signed message is constructed and checked
+    pub attestation: Option<Attestation>,
+}
+
+/// signature scheme used to produce an `Attestation`
+#[cfg(feature = "certificate")]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub enum AttestationScheme {
+    #[default]
+    Secp256k1,
+}
+
+/// a cryptographic attestation that an issuer signed off on a certificate.  The signed
+/// message is the canonical byte encoding produced by
+/// `certificate_verification::canonical_certificate_message`
+#[cfg(feature = "certificate")]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
+pub struct Attestation {
+    /// signature over the canonical certificate message
+    pub signature: Binary,
+    /// public key of the issuer that produced `signature`
+    pub public_key: Binary,
+    /// signature scheme used to produce `signature`, defaults to secp256k1
+    pub scheme: Option<AttestationScheme>,
+}
+
+/// an authorized certificate issuer, keyed by its canonical address in the issuer registry
+#[cfg(feature = "certificate")]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct IssuerInfo {
+    /// human-readable name of the issuer
+    pub name: String,
+    /// public key the issuer signs attestations with
+    pub public_key: Binary,
+    /// true if the issuer is currently allowed to attest new certificates
+    pub enabled: bool,
 }
 
 // recipient information
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct RecipientInfo {
     pub first_name: String,
@@ -138,6 +401,7 @@ pub struct RecipientInfo {
 }
 
 // recipient information
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct ItemInfo {
     pub name: String,
@@ -148,6 +412,7 @@ pub struct ItemInfo {
 }
 
 // issuing organization information
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct Organization {
     pub name: Option<String>,
@@ -156,6 +421,7 @@ pub struct Organization {
 }
 
 // issuing individual information
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct Individual {
     pub name: Option<String>,
@@ -164,6 +430,7 @@ pub struct Individual {
 }
 
 // Additional information. Instructors, classes, etc
+#[cfg(feature = "certificate")]
 #[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug, Default)]
 pub struct Addition {
     pub addition_type: Option<String>,
@@ -172,3 +439,59 @@ pub struct Addition {
     pub individual: Option<Individual>,
     pub organization: Option<Organization>,
 }
+
+#[cfg(test)]
+mod media_source_tests {
+    use super::*;
+
+    fn round_trip(file: &MediaFile) -> MediaFile {
+        let json = serde_json::to_string(file).expect("serialize");
+        serde_json::from_str(&json).expect("deserialize")
+    }
+
+    #[test]
+    fn plain_round_trips() {
+        let file = MediaFile {
+            source: MediaSource::Plain {
+                url: "https://example.com/a.png".to_string(),
+            },
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&file), file);
+    }
+
+    #[test]
+    fn encrypted_round_trips() {
+        let file = MediaFile {
+            source: MediaSource::Encrypted {
+                url: "https://example.com/a.png.enc".to_string(),
+                key: Binary::from(b"key".to_vec()),
+                iv: Binary::from(b"iv".to_vec()),
+                algorithm: Some("AES-CTR".to_string()),
+                ciphertext_hash: Some(Binary::from(vec![1u8; SHA256_DIGEST_LEN])),
+            },
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&file), file);
+    }
+
+    #[test]
+    fn deserializes_legacy_decryption_key() {
+        let json = r#"{"url":"https://example.com/a.png","authentication":{"key":"secret"}}"#;
+        let mf: MediaFile = serde_json::from_str(json).unwrap();
+        match mf.source {
+            MediaSource::Encrypted { key, .. } => assert_eq!(key.as_slice(), b"secret"),
+            _ => panic!("expected Encrypted from legacy decryption key"),
+        }
+    }
+
+    #[test]
+    fn legacy_basic_auth_is_not_mistaken_for_encryption() {
+        let json = r#"{"url":"https://example.com/a.png","authentication":{"user":"alice","key":"password"}}"#;
+        let mf: MediaFile = serde_json::from_str(json).unwrap();
+        match mf.source {
+            MediaSource::Plain { url } => assert_eq!(url, "https://example.com/a.png"),
+            _ => panic!("expected Plain: basic-auth credentials have no MediaSource equivalent"),
+        }
+    }
+}